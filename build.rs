@@ -0,0 +1,24 @@
+use std::process::Command;
+
+fn main() {
+    // sqlx::migrate! embeds the migrations at compile time, so rebuild when they change.
+    println!("cargo:rerun-if-changed=migrations");
+
+    // Build metadata for GET /version. The sha is re-read when HEAD moves;
+    // builds outside a git checkout report "unknown".
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|sha| sha.trim().to_string())
+        .unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    println!(
+        "cargo:rustc-env=BUILD_TIME={}",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    );
+}
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS products (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    price REAL NOT NULL,
+    in_stock BOOLEAN NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sales (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER,
+    discount INTEGER,
+    start_date TEXT,
+    end_date TEXT,
+    FOREIGN KEY(product_id) REFERENCES products(id)
+);
@@ -0,0 +1,6 @@
+-- SQLite can't add a column with a CURRENT_TIMESTAMP default, so existing rows
+-- are backfilled here and inserts set created_at explicitly.
+ALTER TABLE products ADD COLUMN created_at TEXT;
+UPDATE products SET created_at = CURRENT_TIMESTAMP WHERE created_at IS NULL;
+
+ALTER TABLE products ADD COLUMN updated_at TEXT;
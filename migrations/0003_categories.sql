@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL
+);
+
+-- Nullable so existing and uncategorized products keep working.
+ALTER TABLE products ADD COLUMN category_id INTEGER REFERENCES categories(id);
@@ -0,0 +1,2 @@
+-- Deleted products keep their row (and their sales) with the time they were deleted.
+ALTER TABLE products ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,2 @@
+-- Fails if the table already holds duplicate names; rename or merge those first.
+CREATE UNIQUE INDEX IF NOT EXISTS products_name_unique ON products(name);
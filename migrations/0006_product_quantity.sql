@@ -0,0 +1,13 @@
+ALTER TABLE products ADD COLUMN quantity INTEGER NOT NULL DEFAULT 0;
+
+-- Products already flagged as in stock get a nominal count of one so the flag
+-- and the count agree from the start.
+UPDATE products SET quantity = 1 WHERE in_stock;
+
+-- in_stock is derived from quantity from here on; it stays a column so existing
+-- clients and the in_stock filter keep working. Inserts set both columns.
+CREATE TRIGGER IF NOT EXISTS products_in_stock_after_quantity_update
+AFTER UPDATE OF quantity ON products
+BEGIN
+    UPDATE products SET in_stock = NEW.quantity > 0 WHERE id = NEW.id;
+END;
@@ -0,0 +1,2 @@
+-- Bumped on every edit so concurrent updates can detect each other (see update_product).
+ALTER TABLE products ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
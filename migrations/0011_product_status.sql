@@ -0,0 +1,22 @@
+-- Lifecycle status next to the stock flag. `discontinued` is only ever set by
+-- hand; otherwise the status follows the stock level like in_stock does.
+ALTER TABLE products ADD COLUMN status TEXT NOT NULL DEFAULT 'active'
+    CHECK (status IN ('active', 'discontinued', 'out_of_stock'));
+
+UPDATE products SET status = CASE WHEN in_stock THEN 'active' ELSE 'out_of_stock' END;
+
+CREATE TRIGGER IF NOT EXISTS products_status_after_insert
+AFTER INSERT ON products
+WHEN NEW.status <> 'discontinued'
+BEGIN
+    UPDATE products SET status = CASE WHEN NEW.quantity > 0 THEN 'active' ELSE 'out_of_stock' END
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS products_status_after_stock_update
+AFTER UPDATE OF quantity, status ON products
+WHEN NEW.status <> 'discontinued'
+BEGIN
+    UPDATE products SET status = CASE WHEN NEW.quantity > 0 THEN 'active' ELSE 'out_of_stock' END
+    WHERE id = NEW.id;
+END;
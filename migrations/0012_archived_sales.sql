@@ -0,0 +1,12 @@
+-- Expired sales moved out of the hot `sales` table by the archiver. The
+-- original id is kept as sale_id; it isn't the key because an import can
+-- hand the same sale ids out again.
+CREATE TABLE IF NOT EXISTS archived_sales (
+    id INTEGER PRIMARY KEY,
+    sale_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    discount INTEGER NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    archived_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- When several sales for a product are active at once, the highest priority
+-- wins and ties go to the larger discount (see WINNING_SALES in main.rs).
+ALTER TABLE sales ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE archived_sales ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
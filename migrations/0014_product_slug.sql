@@ -0,0 +1,4 @@
+-- URL-friendly product names. Slugs are generated by the application (see
+-- `slugify` in main.rs), which also fills them in for existing rows at startup.
+ALTER TABLE products ADD COLUMN slug TEXT;
+CREATE UNIQUE INDEX products_slug_unique ON products (slug);
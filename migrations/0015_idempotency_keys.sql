@@ -0,0 +1,14 @@
+-- Responses to create requests that carried an Idempotency-Key, replayed when
+-- the same key is sent again. Keys are scoped to the endpoint and expire
+-- after IDEMPOTENCY_KEY_TTL seconds.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    endpoint TEXT NOT NULL,
+    key TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    location TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (endpoint, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at ON idempotency_keys(created_at);
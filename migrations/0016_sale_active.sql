@@ -0,0 +1,4 @@
+-- Paused sales keep their dates but don't apply until resumed (see
+-- PATCH /sales/{id}/active).
+ALTER TABLE sales ADD COLUMN active BOOLEAN NOT NULL DEFAULT 1;
+ALTER TABLE archived_sales ADD COLUMN active BOOLEAN NOT NULL DEFAULT 1;
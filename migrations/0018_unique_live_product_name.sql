@@ -0,0 +1,5 @@
+-- Names only have to be unique among products that aren't soft-deleted, so a
+-- deleted product's name can be reused. Restoring it while the name is taken
+-- fails with a unique violation instead.
+DROP INDEX IF EXISTS products_name_unique;
+CREATE UNIQUE INDEX products_name_unique ON products (name) WHERE deleted_at IS NULL;
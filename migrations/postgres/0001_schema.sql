@@ -0,0 +1,74 @@
+-- The schema of SQLite migrations 0001-0012, written for Postgres in one step
+-- since a Postgres database starts empty. Column names and meanings match the
+-- SQLite tables; see POSTGRES.md for what the application code still needs.
+-- Dates stay TEXT (ISO 8601) because the handlers bind and compare them as
+-- strings.
+
+CREATE TABLE IF NOT EXISTS categories (
+    id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS products (
+    id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    name TEXT NOT NULL,
+    price_cents BIGINT NOT NULL DEFAULT 0,
+    in_stock BOOLEAN NOT NULL,
+    quantity BIGINT NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'discontinued', 'out_of_stock')),
+    description TEXT,
+    image_url TEXT,
+    version BIGINT NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+    updated_at TEXT,
+    category_id INTEGER REFERENCES categories(id),
+    deleted_at TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS products_name_unique ON products(name);
+
+-- Replaces the SQLite AFTER triggers from 0006 and 0011: in_stock always
+-- mirrors quantity, and status follows it unless the product is discontinued.
+CREATE OR REPLACE FUNCTION products_derive_stock() RETURNS trigger AS $$
+BEGIN
+    NEW.in_stock := NEW.quantity > 0;
+    IF NEW.status <> 'discontinued' THEN
+        NEW.status := CASE WHEN NEW.quantity > 0 THEN 'active' ELSE 'out_of_stock' END;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS products_derive_stock ON products;
+CREATE TRIGGER products_derive_stock
+BEFORE INSERT OR UPDATE OF quantity, status ON products
+FOR EACH ROW EXECUTE FUNCTION products_derive_stock();
+
+CREATE TABLE IF NOT EXISTS sales (
+    id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    product_id INTEGER REFERENCES products(id),
+    discount INTEGER,
+    start_date TEXT,
+    end_date TEXT
+);
+
+CREATE TABLE IF NOT EXISTS price_history (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    product_id INTEGER NOT NULL REFERENCES products(id),
+    old_price_cents BIGINT NOT NULL,
+    new_price_cents BIGINT NOT NULL,
+    changed_at TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
+
+CREATE INDEX IF NOT EXISTS price_history_product_id ON price_history(product_id);
+
+CREATE TABLE IF NOT EXISTS archived_sales (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    sale_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    discount INTEGER NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    archived_at TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS')
+);
@@ -0,0 +1,3 @@
+-- Mirrors SQLite migration 0013.
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE archived_sales ADD COLUMN IF NOT EXISTS priority INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Mirrors SQLite migration 0014.
+ALTER TABLE products ADD COLUMN IF NOT EXISTS slug TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS products_slug_unique ON products (slug);
@@ -0,0 +1,3 @@
+-- Mirrors SQLite migration 0016.
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE archived_sales ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,3 @@
+-- Mirrors SQLite migration 0018.
+DROP INDEX IF EXISTS products_name_unique;
+CREATE UNIQUE INDEX products_name_unique ON products (name) WHERE deleted_at IS NULL;
@@ -0,0 +1,68 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn admin_routes_need_the_api_key_and_report_sizes() {
+    let (app, _pool) = setup().await;
+    let app = app.app_data(web::Data::new(ApiKey(Some("secret".into()))));
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post().uri("/admin/vacuum").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/admin/vacuum")
+        .insert_header(("X-API-Key", "secret"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let sizes: Value = test::read_body_json(resp).await;
+    assert!(sizes["before_bytes"].as_i64().unwrap() > 0);
+    assert!(sizes["after_bytes"].as_i64().unwrap() > 0);
+
+    let req = test::TestRequest::get().uri("/admin/db-info").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+    let req = test::TestRequest::get()
+        .uri("/admin/db-info")
+        .insert_header(("X-API-Key", "secret"))
+        .to_request();
+    let info: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        info["size_bytes"].as_i64().unwrap(),
+        info["page_count"].as_i64().unwrap() * info["page_size"].as_i64().unwrap()
+    );
+    assert_eq!(info["journal_mode"], "memory");
+}
+
+#[actix_web::test]
+async fn read_only_mode_refuses_writes_but_serves_reads() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::put()
+        .uri("/admin/read-only")
+        .set_json(json!({ "read_only": true }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Blocked", "price": 1.0 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "service is in read-only mode");
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // The toggle itself still works, so the mode can be lifted.
+    let req = test::TestRequest::put()
+        .uri("/admin/read-only")
+        .set_json(json!({ "read_only": false }))
+        .to_request();
+    let state: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(state["read_only"], false);
+}
@@ -0,0 +1,239 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn catalog_inlines_active_sales() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for (name, price) in [("Discounted", 19.99), ("Full price", 5.0)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": price }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": 1,
+            "discount": 10,
+            "start_date": today(),
+            "end_date": today()
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/catalog").to_request();
+    let catalog: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(catalog[0]["name"], "Discounted");
+    assert_eq!(catalog[0]["active_sale"], json!({ "discount": 10, "final_price": 17.99 }));
+    assert_eq!(catalog[1]["name"], "Full price");
+    assert_eq!(catalog[1]["active_sale"], Value::Null);
+}
+
+#[actix_web::test]
+async fn deals_are_ordered_by_best_discount() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for (name, discount) in [("Small", 5), ("Big", 50), ("Medium", 20)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 10 }))
+            .to_request();
+        let product: Product = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": product.id,
+                "discount": discount,
+                "start_date": today(),
+                "end_date": today()
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let req = test::TestRequest::get().uri("/deals").to_request();
+    let deals: Value = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = deals
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|deal| deal["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Big", "Medium", "Small"]);
+    assert_eq!(deals[0]["base_price"], 10.0);
+    assert_eq!(deals[0]["final_price"], 5.0);
+
+    let req = test::TestRequest::get().uri("/deals?limit=1").to_request();
+    let top: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(top.as_array().unwrap().len(), 1);
+    assert_eq!(top[0]["name"], "Big");
+}
+
+#[actix_web::test]
+async fn highest_priority_active_sale_sets_the_price() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Promoted", "price": 100 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    // The deeper discount loses to the higher-priority one.
+    for (discount, priority) in [(40, 0), (15, 5)] {
+        let req = test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": 1,
+                "discount": discount,
+                "priority": priority,
+                "start_date": today(),
+                "end_date": today()
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    // Overlapping at an existing priority is still ambiguous.
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": 1,
+            "discount": 20,
+            "priority": 5,
+            "start_date": today(),
+            "end_date": today()
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::get().uri("/products/1/price").to_request();
+    let price: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(price["discount"], 15);
+    assert_eq!(price["final_price"], 85.0);
+
+    let req = test::TestRequest::get().uri("/catalog").to_request();
+    let catalog: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(catalog[0]["active_sale"]["discount"], 15);
+
+    let req = test::TestRequest::get().uri("/deals").to_request();
+    let deals: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(deals.as_array().unwrap().len(), 1);
+    assert_eq!(deals[0]["discount"], 15);
+}
+
+#[actix_web::test]
+async fn paused_sales_stop_applying_until_resumed() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Paused", "price": 50 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({ "product_id": 1, "discount": 20, "start_date": today(), "end_date": today() }))
+        .to_request();
+    let sale: Sale = test::call_and_read_body_json(&app, req).await;
+    assert!(sale.active);
+
+    let set_active = |active: bool| {
+        test::TestRequest::patch()
+            .uri(&format!("/sales/{}/active", sale.id))
+            .set_json(json!({ "active": active }))
+            .to_request()
+    };
+    let paused: Sale = test::call_and_read_body_json(&app, set_active(false)).await;
+    assert!(!paused.active);
+
+    let req = test::TestRequest::get().uri("/sales/active").to_request();
+    let active: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert!(active.is_empty());
+    let req = test::TestRequest::get().uri("/products/1/price").to_request();
+    let price: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(price["final_price"], 50.0);
+
+    test::call_service(&app, set_active(true)).await;
+    let req = test::TestRequest::get().uri("/products/1/price").to_request();
+    let price: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(price["final_price"], 40.0);
+}
+
+#[actix_web::test]
+async fn stats_round_the_average_price() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for (name, price) in [("One", 0.01), ("Two", 0.01), ("Three", 0.02)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": price }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let req = test::TestRequest::get().uri("/stats").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert!(String::from_utf8_lossy(&body).contains("\"average_price\":0.01,"));
+}
+
+#[actix_web::test]
+async fn inventory_value_sums_in_stock_products() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/stats/inventory-value").to_request();
+    let empty: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(empty, json!({ "total_value": 0.0, "product_count": 0 }));
+
+    for (name, price, quantity) in [("Chair", 12.5, 4), ("Desk", 80.0, 2), ("Sold out", 99.0, 0)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": price, "quantity": quantity }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({ "product_id": 2, "discount": 25, "start_date": today(), "end_date": today() }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/stats/inventory-value").to_request();
+    let value: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(value, json!({ "total_value": 210.0, "product_count": 2 }));
+
+    let req = test::TestRequest::get()
+        .uri("/stats/inventory-value?apply_discounts=true")
+        .to_request();
+    let value: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(value["total_value"], 170.0);
+}
+
+#[actix_web::test]
+async fn catalog_includes_product_tags() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Tagged", "price": 4.0 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::post()
+        .uri("/products/1/tags")
+        .set_json(json!({ "tags": ["new"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get().uri("/catalog").to_request();
+    let catalog: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(catalog[0]["tags"], json!(["new"]));
+}
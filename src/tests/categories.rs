@@ -0,0 +1,54 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn categories_filter_products_and_are_deleted_once_unused() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/categories")
+        .set_json(json!({ "name": "Kitchen" }))
+        .to_request();
+    let kitchen: Value = test::call_and_read_body_json(&app, req).await;
+    let category_id = kitchen["id"].as_i64().unwrap();
+    for (name, category) in [("Mug", Some(category_id)), ("Lamp", None)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 5, "category_id": category }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products?category_id={}", category_id))
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["name"], "Mug");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/categories/{}", category_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Category is still assigned to 1 product(s)");
+
+    // A deleted product no longer holds the category.
+    let req = test::TestRequest::delete().uri("/delete-product/1").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::delete()
+        .uri(&format!("/categories/{}", category_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get().uri("/categories").to_request();
+    let categories: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(categories, json!([]));
+    let req = test::TestRequest::delete()
+        .uri(&format!("/categories/{}", category_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
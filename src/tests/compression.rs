@@ -0,0 +1,37 @@
+use super::*;
+use actix_web::test;
+use serde_json::json;
+
+#[actix_web::test]
+async fn responses_are_compressed_when_the_client_accepts_it() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Teapot", "price": 12, "quantity": 1 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri("/products")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+    let req = test::TestRequest::get()
+        .uri("/products.csv")
+        .insert_header((header::ACCEPT_ENCODING, "br"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "br");
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/csv; charset=utf-8"
+    );
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+}
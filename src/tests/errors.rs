@@ -0,0 +1,58 @@
+use super::*;
+use sqlx::error::DatabaseError;
+use std::borrow::Cow;
+
+/// Stands in for the error SQLite reports, so classification can be tested
+/// without provoking real lock contention.
+#[derive(Debug)]
+struct FakeSqliteError(&'static str);
+
+impl fmt::Display for FakeSqliteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "simulated SQLite error {}", self.0)
+    }
+}
+
+impl std::error::Error for FakeSqliteError {}
+
+impl DatabaseError for FakeSqliteError {
+    fn message(&self) -> &str {
+        "database is locked"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.0))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+}
+
+fn sqlite_error(code: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(FakeSqliteError(code)))
+}
+
+#[test]
+fn busy_and_locked_errors_become_a_retryable_503() {
+    // SQLITE_BUSY, SQLITE_LOCKED and SQLITE_BUSY_TIMEOUT.
+    for code in ["5", "6", "773"] {
+        let err = ApiError::from(sqlite_error(code));
+        assert!(matches!(err, ApiError::DatabaseBusy), "code {}", code);
+        let response = err.error_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "1");
+    }
+
+    // A unique violation is not a lock, and still a plain database error here.
+    assert!(matches!(ApiError::from(sqlite_error(SQLITE_CONSTRAINT_UNIQUE)), ApiError::Database(_)));
+    assert!(matches!(ApiError::from(sqlx::Error::RowNotFound), ApiError::Database(_)));
+}
@@ -0,0 +1,45 @@
+use super::*;
+use actix_web::test;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn probes_report_live_and_ready() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for (uri, status) in [("/livez", "ok"), ("/readyz", "ready")] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["status"], status);
+    }
+}
+
+#[actix_web::test]
+async fn readyz_is_unavailable_before_migrations() {
+    let (app, _pool) = setup().await;
+    let unmigrated = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let app = test::init_service(app.app_data(web::Data::new(unmigrated))).await;
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let req = test::TestRequest::get().uri("/livez").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn version_reports_the_package_version() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/version").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(!body["git_sha"].as_str().unwrap().is_empty());
+}
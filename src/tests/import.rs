@@ -0,0 +1,67 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+async fn row_counts(pool: &SqlitePool) -> Vec<i64> {
+    let mut counts = Vec::new();
+    for table in ["categories", "products", "sales", "price_history"] {
+        let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        counts.push(count);
+    }
+    counts
+}
+
+fn product(id: i32, name: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "price": 5,
+        "in_stock": true,
+        "quantity": 1,
+        "version": 1,
+        "created_at": "2024-01-01 00:00:00"
+    })
+}
+
+#[actix_web::test]
+async fn dry_run_import_reports_changes_and_leaves_the_database_untouched() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["Kept", "Dropped"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 10, "quantity": 2 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let before = row_counts(&pool).await;
+
+    let req = test::TestRequest::post()
+        .uri("/import?dry_run=true")
+        .set_json(json!({
+            "categories": [],
+            "products": [product(1, "Kept"), product(7, "New"), product(8, "New")],
+            "sales": []
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let summary: Value = test::read_body_json(resp).await;
+    assert_eq!(summary["dry_run"], true);
+    assert_eq!(
+        summary["products"],
+        json!({ "inserted": 1, "updated": 1, "removed": 1, "conflicts": 1 })
+    );
+    assert_eq!(summary["conflicts"].as_array().unwrap().len(), 1);
+
+    assert_eq!(row_counts(&pool).await, before);
+    let names: Vec<(String,)> = sqlx::query_as("SELECT name FROM products ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(names, [("Kept".to_string(),), ("Dropped".to_string(),)]);
+}
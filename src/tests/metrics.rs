@@ -0,0 +1,23 @@
+use super::*;
+use actix_web::test;
+
+#[actix_web::test]
+async fn metrics_are_labelled_by_route_pattern() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for uri in ["/products/1", "/products/2", "/nonexistent"] {
+        test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    }
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains(
+        r#"http_requests_total{method="GET",route="/products/{id}",status="404"} 2"#
+    ));
+    assert!(body.contains(r#"route="unmatched",status="404"} 1"#));
+    assert!(!body.contains("/products/1"));
+    assert!(body.contains("db_pool_connections_in_use"));
+}
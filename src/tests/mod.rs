@@ -0,0 +1,114 @@
+//! End-to-end tests that run requests through the real routes against an
+//! in-memory database. Start new scenarios from `setup()`.
+
+mod admin;
+mod catalog;
+mod categories;
+mod compression;
+mod errors;
+mod health;
+mod import;
+mod metrics;
+mod pretty;
+mod products;
+mod rate_limit;
+mod request_id;
+mod rounding;
+mod routing;
+mod seed;
+mod sales;
+mod schema;
+mod webhooks;
+
+use super::*;
+use actix_web::dev::ServiceFactory;
+
+/// A fresh in-memory database with all migrations applied. Every
+/// `sqlite::memory:` connection is its own database, so the pool holds exactly
+/// one connection and never recycles it.
+pub(crate) async fn test_pool() -> SqlitePool {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:").expect("valid in-memory URL");
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .expect("in-memory database opens");
+    init_db(&pool).await.expect("migrations apply");
+    pool
+}
+
+/// A fresh database file in the temp directory, opened with `connect_pool` like
+/// in production, for tests that need several connections writing at once.
+/// The files are removed when the returned guard is dropped.
+pub(crate) async fn file_pool() -> (SqlitePool, TempDatabase) {
+    let path = env::temp_dir().join(format!("shop_test_{}.sqlite", Uuid::new_v4()));
+    let pool = connect_pool(&format!("sqlite://{}?mode=rwc", path.display()))
+        .await
+        .expect("temporary database opens");
+    init_db(&pool).await.expect("migrations apply");
+    (pool, TempDatabase(path))
+}
+
+pub(crate) struct TempDatabase(std::path::PathBuf);
+
+impl Drop for TempDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.0.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// The app as `main` builds it, minus CORS and request logging, on a fresh
+/// database. No API key is required, rate limiting and the product cache are
+/// off, sales don't require stock and the discount cap is the default; tests
+/// can override any of these by adding their own `app_data`. The pool is
+/// returned too for setting up or inspecting rows directly.
+pub(crate) async fn setup() -> (
+    App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<impl MessageBody>,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    >,
+    SqlitePool,
+) {
+    let pool = test_pool().await;
+    let app = App::new()
+        .wrap(from_fn(require_api_key))
+        .wrap(from_fn(rate_limit_writes))
+        .wrap(from_fn(reject_writes_when_read_only))
+        .wrap(from_fn(pretty_print_json))
+        .wrap(Compress::default())
+        .wrap(from_fn(record_metrics))
+        .wrap(from_fn(assign_request_id))
+        .wrap(NormalizePath::trim())
+        .app_data(web::Data::new(pool.clone()))
+        .app_data(web::Data::new(ApiKey(None)))
+        .app_data(web::Data::new(RateLimiter {
+            limit: 0,
+            windows: Mutex::new(HashMap::new()),
+        }))
+        .app_data(web::Data::new(ProductsCache {
+            ttl: Duration::ZERO,
+            state: Mutex::new(ProductsCacheState::default()),
+        }))
+        .app_data(web::Data::new(SalePolicy {
+            require_stock: false,
+            max_discount: DEFAULT_MAX_DISCOUNT,
+        }))
+        .app_data(web::Data::new(Metrics::new()))
+        .app_data(web::Data::new(LowStockThreshold(DEFAULT_LOW_STOCK_THRESHOLD)))
+        .app_data(web::Data::new(ReadOnlyMode(AtomicBool::new(false))))
+        .app_data(web::Data::new(Webhooks::new(None)))
+        .app_data(web::Data::new(IdempotencyWindow(Duration::from_secs(DEFAULT_IDEMPOTENCY_KEY_TTL_SECS))))
+        .configure(|cfg| configure_api(cfg, DEFAULT_MAX_JSON_BODY));
+    (app, pool)
+}
@@ -0,0 +1,31 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn pretty_param_indents_json_responses() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Globe", "price": 40.0 }))
+        .to_request();
+    let created: Product = test::call_and_read_body_json(&app, req).await;
+
+    let uri = format!("/products/{}", created.id);
+    let req = test::TestRequest::get().uri(&uri).to_request();
+    let compact = test::call_and_read_body(&app, req).await;
+    assert!(!compact.contains(&b'\n'));
+
+    let req = test::TestRequest::get().uri(&format!("{}?pretty=true", uri)).to_request();
+    let pretty = test::call_and_read_body(&app, req).await;
+    assert!(pretty.contains(&b'\n'));
+    assert_eq!(
+        serde_json::from_slice::<Value>(&pretty).unwrap(),
+        serde_json::from_slice::<Value>(&compact).unwrap()
+    );
+
+    let req = test::TestRequest::get().uri("/products?pretty=true").to_request();
+    assert!(test::call_and_read_body(&app, req).await.contains(&b'\n'));
+}
@@ -0,0 +1,1121 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn added_product_can_be_fetched_and_deleted() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "USB Cable", "price": 4.99, "quantity": 3 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: Product = test::read_body_json(resp).await;
+    assert_eq!(created.name, "USB Cable");
+    assert_eq!(created.price_cents, 499);
+    assert!(created.in_stock);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}", created.id))
+        .to_request();
+    let fetched: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fetched.id, created.id);
+    assert_eq!(fetched.quantity, 3);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/delete-product/{}", created.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}", created.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn deleting_a_missing_product_is_not_found() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::delete().uri("/delete-product/42").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn adding_the_same_name_twice_is_a_conflict() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for expected in [StatusCode::CREATED, StatusCode::CONFLICT] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": "Desk Lamp", "price": 25 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+        if expected == StatusCode::CONFLICT {
+            let body: Value = test::read_body_json(resp).await;
+            assert_eq!(body["error"], "a product with that name already exists");
+        }
+    }
+}
+
+#[actix_web::test]
+async fn deleted_names_can_be_reused_but_block_restoring() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let add = || {
+        test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": "USB Cable", "price": 4.99 }))
+            .to_request()
+    };
+    let original: Product = test::call_and_read_body_json(&app, add()).await;
+    let req = test::TestRequest::delete()
+        .uri(&format!("/delete-product/{}", original.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(test::call_service(&app, add()).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/restore", original.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["error"],
+        "another product already uses this name; rename it before restoring"
+    );
+}
+
+#[actix_web::test]
+async fn restoring_a_product_bumps_its_version() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Stapler", "price": 12 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::delete()
+        .uri(&format!("/delete-product/{}", product.id))
+        .to_request();
+    test::call_service(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/restore", product.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", product.id))
+        .set_json(json!({ "price": 13, "version": product.version }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn negative_price_is_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Broken", "price": -1 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn description_and_image_url_are_optional_and_persisted() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({
+            "name": "Lamp",
+            "price": 25,
+            "description": "A desk lamp",
+            "image_url": "https://cdn.example.com/lamp.png"
+        }))
+        .to_request();
+    let created: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(created.description.as_deref(), Some("A desk lamp"));
+    assert_eq!(created.image_url.as_deref(), Some("https://cdn.example.com/lamp.png"));
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Plain", "price": 1 }))
+        .to_request();
+    let plain: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(plain.description, None);
+    assert_eq!(plain.image_url, None);
+}
+
+#[actix_web::test]
+async fn non_web_image_urls_are_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for image_url in ["javascript:alert(1)", "not a url", "/relative.png"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": "Lamp", "price": 25, "image_url": image_url }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", image_url);
+    }
+}
+
+#[actix_web::test]
+async fn stale_update_is_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Mug", "price": 8 }))
+        .to_request();
+    let created: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(created.version, 1);
+
+    // The first admin saves against version 1...
+    let req = test::TestRequest::put()
+        .uri(&format!("/update-product/{}", created.id))
+        .set_json(json!({ "name": "Mug", "price": 9, "version": 1 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+    // ...so the second admin, still holding version 1, gets a conflict.
+    let req = test::TestRequest::put()
+        .uri(&format!("/update-product/{}", created.id))
+        .insert_header((header::IF_MATCH, "\"1\""))
+        .set_json(json!({ "name": "Mug", "price": 7 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}", created.id))
+        .to_request();
+    let current: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(current.price_cents, 900);
+    assert_eq!(current.version, 2);
+}
+
+#[actix_web::test]
+async fn update_without_a_version_is_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Mug", "price": 8 }))
+        .to_request();
+    let created: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/update-product/{}", created.id))
+        .set_json(json!({ "name": "Mug", "price": 9 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn price_changes_are_recorded_in_order() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Kettle", "price": 20 }))
+        .to_request();
+    let created: Product = test::call_and_read_body_json(&app, req).await;
+
+    for (version, price) in [(1, 25.5), (2, 19.99)] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/update-product/{}", created.id))
+            .set_json(json!({ "name": "Kettle", "price": price, "version": version }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}/price-history", created.id))
+        .to_request();
+    let history: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["old_price"], 20.0);
+    assert_eq!(history[0]["new_price"], 25.5);
+    assert_eq!(history[1]["old_price"], 25.5);
+    assert_eq!(history[1]["new_price"], 19.99);
+}
+
+#[actix_web::test]
+async fn prices_are_tagged_with_the_currency() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Priced", "price": 3.5, "quantity": 1 }))
+        .to_request();
+    let product: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product["currency"], "USD");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}/price", product["id"]))
+        .to_request();
+    let price: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(price["currency"], "USD");
+    assert_eq!(price["final_price"], 3.5);
+}
+
+#[actix_web::test]
+async fn clone_copies_the_product_under_a_new_name() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Lamp", "price": 20, "quantity": 3, "description": "Brass" }))
+        .to_request();
+    let original: Product = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": original.id,
+            "discount": 10,
+            "start_date": "2030-01-01",
+            "end_date": "2030-01-31"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/clone", original.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let copy: Product = test::read_body_json(resp).await;
+    assert_ne!(copy.id, original.id);
+    assert_eq!(copy.name, "Lamp (copy)");
+    assert_eq!(copy.price_cents, original.price_cents);
+    assert_eq!(copy.description.as_deref(), Some("Brass"));
+
+    let (sales,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sales WHERE product_id = ?")
+        .bind(copy.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(sales, 0);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/clone", original.id))
+        .to_request();
+    let second: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(second.name, "Lamp (copy 2)");
+
+    let req = test::TestRequest::post().uri("/products/999/clone").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "x".repeat(MAX_PRODUCT_NAME_LEN), "price": 1 }))
+        .to_request();
+    let long: Product = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/clone", long.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn patch_updates_only_the_given_fields() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Kettle", "price": 30, "quantity": 4 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", product.id))
+        .set_json(json!({ "price": 24.5 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get(header::ETAG).unwrap(), "\"2\"");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}", product.id))
+        .to_request();
+    let patched: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.price_cents, 2450);
+    assert_eq!(patched.name, "Kettle");
+    assert_eq!(patched.quantity, 4);
+    assert!(patched.in_stock);
+    assert_eq!(patched.version, 2);
+}
+
+#[actix_web::test]
+async fn patch_without_fields_is_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Kettle", "price": 30 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", product.id))
+        .set_json(json!({ "version": 1 }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "no fields to update");
+}
+
+#[actix_web::test]
+async fn cursor_pagination_walks_every_product_once() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for i in 0..7 {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": format!("Item {}", i), "price": 1 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let mut seen = Vec::new();
+    let mut uri = "/products?limit=3".to_string();
+    loop {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        let page: Value = test::call_and_read_body_json(&app, req).await;
+        for item in page["items"].as_array().unwrap() {
+            seen.push(item["id"].as_i64().unwrap());
+        }
+        match page["next_cursor"].as_i64() {
+            Some(cursor) => uri = format!("/products?limit=3&after={}", cursor),
+            None => break,
+        }
+    }
+    let mut expected = seen.clone();
+    expected.sort();
+    expected.dedup();
+    assert_eq!(seen.len(), 7);
+    assert_eq!(seen, expected);
+
+    let req = test::TestRequest::get().uri("/products?after=1&offset=2").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn unknown_sort_gets_a_json_error() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/products?sort=bogus").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "bad_request");
+    assert!(body["error"].as_str().unwrap().contains("unknown variant `bogus`"));
+}
+
+#[actix_web::test]
+async fn unknown_status_is_rejected() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Fruit", "price": 1, "status": "banana" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get().uri("/products?status=banana").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "bad_request");
+}
+
+#[actix_web::test]
+async fn status_follows_stock_unless_discontinued() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Empty", "price": 1, "quantity": 0 }))
+        .to_request();
+    let empty: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(empty.status, ProductStatus::OutOfStock);
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Retired", "price": 1, "quantity": 5, "status": "discontinued" }))
+        .to_request();
+    let retired: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(retired.status, ProductStatus::Discontinued);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", empty.id))
+        .set_json(json!({ "quantity": 2 }))
+        .to_request();
+    let restocked: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(restocked.status, ProductStatus::Active);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", retired.id))
+        .set_json(json!({ "quantity": 0 }))
+        .to_request();
+    let still_retired: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(still_retired.status, ProductStatus::Discontinued);
+
+    let req = test::TestRequest::get().uri("/products?status=discontinued").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["status"], "discontinued");
+}
+
+#[actix_web::test]
+async fn concurrent_reservations_never_oversell() {
+    // The in-memory pool has a single connection and would run the
+    // reservations one after another; this one lets them race for the lock.
+    let (pool, _db) = file_pool().await;
+    let (app, _) = setup().await;
+    let app = test::init_service(app.app_data(web::Data::new(pool.clone()))).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Limited", "price": 50, "quantity": 5 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let reserve = |qty: i64| {
+        let req = test::TestRequest::post()
+            .uri(&format!("/products/{}/reserve", product.id))
+            .set_json(json!({ "qty": qty }))
+            .to_request();
+        test::call_service(&app, req)
+    };
+    let (a, b, c) = tokio::join!(reserve(2), reserve(2), reserve(2));
+    let statuses = [a.status(), b.status(), c.status()];
+    assert_eq!(statuses.iter().filter(|s| **s == StatusCode::OK).count(), 2);
+    assert_eq!(statuses.iter().filter(|s| **s == StatusCode::CONFLICT).count(), 1);
+    assert!(pool.size() > 1, "the reservations should have run on separate connections");
+
+    let (quantity,): (i64,) = sqlx::query_as("SELECT quantity FROM products WHERE id = ?")
+        .bind(product.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(quantity, 1);
+
+    let resp = reserve(2).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "not enough stock to reserve 2 unit(s); 1 remaining");
+}
+
+#[actix_web::test]
+async fn batch_fetch_keeps_request_order_and_skips_missing_ids() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let mut ids = Vec::new();
+    for name in ["First", "Second", "Third"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 2 }))
+            .to_request();
+        let product: Product = test::call_and_read_body_json(&app, req).await;
+        ids.push(product.id);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/batch?ids={},999,{}", ids[2], ids[0]))
+        .to_request();
+    let products: Vec<Product> = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = products.iter().map(|product| product.name.as_str()).collect();
+    assert_eq!(names, ["Third", "First"]);
+
+    let too_many = (1..=101).map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    for uri in ["/products/batch?ids=1,x".to_string(), format!("/products/batch?ids={}", too_many)] {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[actix_web::test]
+async fn product_names_are_trimmed_and_bounded() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let long_name = "x".repeat(300);
+    for name in ["   ", long_name.as_str()] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 1 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "  Teapot \n", "price": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product.name, "Teapot");
+
+    // The trimmed name is what the unique index sees.
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Teapot  ", "price": 1 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+}
+
+#[actix_web::test]
+async fn low_stock_includes_the_threshold_itself() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for (name, quantity) in [("Plenty", 6), ("At default", 5), ("Scarce", 1), ("Gone", 0)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 1, "quantity": quantity }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let names = |products: Vec<Product>| products.into_iter().map(|p| p.name).collect::<Vec<_>>();
+    let req = test::TestRequest::get().uri("/products/low-stock").to_request();
+    let products: Vec<Product> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(names(products), ["Gone", "Scarce", "At default"]);
+
+    let req = test::TestRequest::get().uri("/products/low-stock?threshold=1").to_request();
+    let products: Vec<Product> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(names(products), ["Gone", "Scarce"]);
+}
+
+#[actix_web::test]
+async fn colliding_slugs_get_numeric_suffixes() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let mut slugs = Vec::new();
+    for name in ["Blue Mug", "Blue  Mug!", "blue-mug"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 8.0 }))
+            .to_request();
+        let created: Product = test::call_and_read_body_json(&app, req).await;
+        slugs.push(created.slug.unwrap());
+    }
+    assert_eq!(slugs, ["blue-mug", "blue-mug-2", "blue-mug-3"]);
+
+    let req = test::TestRequest::get().uri("/products/by-slug/blue-mug-2").to_request();
+    let fetched: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fetched.name, "Blue  Mug!");
+
+    // Renaming regenerates the slug and frees the old one.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/products/{}", fetched.id))
+        .set_json(json!({ "name": "Red Mug" }))
+        .to_request();
+    let renamed: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(renamed.slug.as_deref(), Some("red-mug"));
+
+    let req = test::TestRequest::get().uri("/products/by-slug/blue-mug-2").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn fields_restricts_each_listed_product() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Lamp", "price": 30.0, "description": "Warm light" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/products?fields=name,price").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["items"], json!([{ "name": "Lamp", "price": 30.0 }]));
+    assert_eq!(page["total"], 1);
+
+    let req = test::TestRequest::get().uri("/products?fields=name,price_cents").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("price_cents"));
+}
+
+#[actix_web::test]
+async fn repeated_idempotency_key_replays_the_first_response() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .insert_header(("Idempotency-Key", "retry-1"))
+            .set_json(json!({ "name": "Teapot", "price": 18.0 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        bodies.push(test::read_body(resp).await);
+    }
+    assert_eq!(bodies[0], bodies[1]);
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products WHERE name = 'Teapot'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .insert_header(("Idempotency-Key", "retry 2"))
+        .set_json(json!({ "name": "Teacup", "price": 6.0 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn tags_filter_products_and_can_be_detached() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["Fresh", "Old stock"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 3.0 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let req = test::TestRequest::post()
+        .uri("/products/1/tags")
+        .set_json(json!({ "tags": ["New", "sale"] }))
+        .to_request();
+    let attached: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(attached["tags"], json!(["new", "sale"]));
+
+    let req = test::TestRequest::get().uri("/products?tag=new").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["name"], "Fresh");
+    assert_eq!(page["items"][0]["tags"], json!(["new", "sale"]));
+
+    let req = test::TestRequest::delete().uri("/products/1/tags/new").to_request();
+    let remaining: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(remaining["tags"], json!(["sale"]));
+
+    let req = test::TestRequest::get().uri("/products?tag=new").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 0);
+
+    let req = test::TestRequest::delete().uri("/products/1/tags/new").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn stock_reconciler_clears_in_stock_when_quantity_is_zero() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Drifted", "price": 5, "quantity": 0 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    // Writing in_stock directly skips the quantity trigger, as a buggy path would.
+    sqlx::query("UPDATE products SET in_stock = 1 WHERE id = ?")
+        .bind(product.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(reconcile_stock_flags(&pool).await.unwrap(), 1);
+    let (in_stock,): (bool,) = sqlx::query_as("SELECT in_stock FROM products WHERE id = ?")
+        .bind(product.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(!in_stock);
+    assert_eq!(reconcile_stock_flags(&pool).await.unwrap(), 0);
+}
+
+#[actix_web::test]
+async fn malformed_prices_are_rejected_on_add_and_update() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for price in [json!(-5.0), Value::Null, json!("free")] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": "Broken", "price": price }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Mug", "price": 8 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::put()
+        .uri(&format!("/update-product/{}", product.id))
+        .set_json(json!({ "name": "Mug", "price": -5.0, "version": product.version }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn bulk_insert_is_all_or_nothing() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+    let bulk = |products: Value| {
+        test::TestRequest::post()
+            .uri("/products/bulk")
+            .set_json(products)
+            .to_request()
+    };
+    let count = || async {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        count
+    };
+
+    let summary: Value = test::call_and_read_body_json(
+        &app,
+        bulk(json!([{ "name": "Pen", "price": 1.5 }, { "name": "Pad", "price": 3 }])),
+    )
+    .await;
+    assert_eq!(summary, json!({ "inserted": 2 }));
+
+    let resp = test::call_service(
+        &app,
+        bulk(json!([{ "name": "Ink", "price": 2 }, { "name": "Nib", "price": -1 }])),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(count().await, 2);
+
+    // "Pen" only fails on insert, after "Ink" went in, so this exercises the rollback.
+    let resp = test::call_service(
+        &app,
+        bulk(json!([{ "name": "Ink", "price": 2 }, { "name": "Pen", "price": 1 }])),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(count().await, 2);
+
+    let summary: Value = test::call_and_read_body_json(&app, bulk(json!([]))).await;
+    assert_eq!(summary, json!({ "inserted": 0 }));
+    assert_eq!(count().await, 2);
+}
+
+#[actix_web::test]
+async fn csv_export_quotes_names_with_commas_and_quotes() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Widget, \"Deluxe\"", "price": 12.5, "quantity": 2 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/products.csv").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "text/csv; charset=utf-8");
+    assert_eq!(
+        resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+        "attachment; filename=\"products.csv\""
+    );
+    let body = test::read_body(resp).await;
+    assert_eq!(
+        std::str::from_utf8(&body).unwrap(),
+        "id,name,price,in_stock,quantity\r\n1,\"Widget, \"\"Deluxe\"\"\",12.50,true,2\r\n"
+    );
+}
+
+#[actix_web::test]
+async fn stock_adjustments_cannot_go_below_zero() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Counted", "price": 3, "quantity": 2 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let adjust = |delta: i64| {
+        test::TestRequest::post()
+            .uri(&format!("/products/{}/adjust-stock", product.id))
+            .set_json(json!({ "delta": delta }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, adjust(-3)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("current quantity is 2"));
+
+    let emptied: Product = test::call_and_read_body_json(&app, adjust(-2)).await;
+    assert_eq!(emptied.quantity, 0);
+    assert!(!emptied.in_stock);
+    let restocked: Product = test::call_and_read_body_json(&app, adjust(5)).await;
+    assert_eq!(restocked.quantity, 5);
+    assert!(restocked.in_stock);
+}
+
+#[actix_web::test]
+async fn bulk_delete_counts_only_existing_products() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["Keep", "Drop one", "Drop two"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 1 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    let bulk_delete = |ids: Value| {
+        test::TestRequest::post()
+            .uri("/products/bulk-delete")
+            .set_json(ids)
+            .to_request()
+    };
+
+    let summary: Value = test::call_and_read_body_json(&app, bulk_delete(json!([2, 3, 3, 999]))).await;
+    assert_eq!(summary, json!({ "deleted": 2 }));
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["name"], "Keep");
+
+    let resp = test::call_service(&app, bulk_delete(json!([]))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn product_writes_bust_the_list_cache() {
+    let (app, pool) = setup().await;
+    let app = app.app_data(web::Data::new(ProductsCache {
+        ttl: Duration::from_secs(60),
+        state: Mutex::new(ProductsCacheState::default()),
+    }));
+    let app = test::init_service(app).await;
+    let total = || async {
+        let req = test::TestRequest::get().uri("/products").to_request();
+        let page: Value = test::call_and_read_body_json(&app, req).await;
+        page["total"].clone()
+    };
+
+    assert_eq!(total().await, 0);
+    // A write that skips the handlers isn't seen until the entry expires...
+    sqlx::query("INSERT INTO products (name, price_cents, in_stock) VALUES ('Hidden', 100, 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(total().await, 0);
+
+    // ...but one through the API clears the cache straight away.
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Fresh", "price": 2 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    assert_eq!(total().await, 2);
+}
+
+#[actix_web::test]
+async fn product_list_answers_304_for_a_matching_etag() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Tagged", "price": 2 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = resp.headers().get(header::ETAG).unwrap().clone();
+
+    let req = test::TestRequest::get()
+        .uri("/products")
+        .insert_header((header::IF_NONE_MATCH, etag.clone()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert!(test::read_body(resp).await.is_empty());
+
+    let req = test::TestRequest::get()
+        .uri("/products")
+        .insert_header((header::IF_NONE_MATCH, "\"stale\""))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn prices_are_stored_as_exact_cents() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Pocket change", "price": 0.1 + 0.2 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product.price_cents, 30);
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Discounted", "price": 19.99 }))
+        .to_request();
+    let discounted: Product = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": discounted.id,
+            "discount": 10,
+            "start_date": today(),
+            "end_date": today()
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/products/{}/price", discounted.id))
+        .to_request();
+    let price: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(price["base_price"], 19.99);
+    // 19.99 * 0.9 is 17.991; integer math rounds it to the cent without drift.
+    assert_eq!(price["final_price"], 17.99);
+}
+
+#[actix_web::test]
+async fn tag_changes_move_the_product_etag_on() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Mug", "price": 3.0 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = test::TestRequest::get().uri("/products/1").to_request();
+    let etag = test::call_service(&app, req).await.headers().get(header::ETAG).unwrap().clone();
+
+    let req = test::TestRequest::post()
+        .uri("/products/1/tags")
+        .set_json(json!({ "tags": ["new"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get().uri("/products/1").to_request();
+    let resp = test::call_service(&app, req).await;
+    let tagged_etag = resp.headers().get(header::ETAG).unwrap().clone();
+    assert_ne!(tagged_etag, etag);
+
+    let req = test::TestRequest::delete().uri("/products/1/tags/new").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get().uri("/products/1").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.headers().get(header::ETAG).unwrap(), tagged_etag);
+}
+
+#[actix_web::test]
+async fn search_ignores_case_and_an_empty_term_matches_everything() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["USB Cable", "Desk Lamp"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 5 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let req = test::TestRequest::get().uri("/products?search=uSb%20cAbLe").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["name"], "USB Cable");
+
+    for uri in ["/products?search=", "/products?search=%20", "/products"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let page: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(page["total"], 2, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn patch_null_clears_a_field_but_leaving_it_out_keeps_it() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/categories")
+        .set_json(json!({ "name": "Kitchen" }))
+        .to_request();
+    let category: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({
+            "name": "Mug",
+            "price": 4,
+            "description": "Holds coffee",
+            "image_url": "https://img.example/mug.png",
+            "category_id": category["id"]
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::patch()
+        .uri("/products/1")
+        .set_json(json!({ "description": null, "category_id": null }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let patched: Value = test::read_body_json(resp).await;
+    assert_eq!(patched["description"], Value::Null);
+    assert_eq!(patched["category_id"], Value::Null);
+    assert_eq!(patched["image_url"], "https://img.example/mug.png");
+
+    let req = test::TestRequest::patch()
+        .uri("/products/1")
+        .set_json(json!({ "price": 5 }))
+        .to_request();
+    let patched: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patched["image_url"], "https://img.example/mug.png");
+}
@@ -0,0 +1,35 @@
+use super::*;
+use actix_web::test;
+use serde_json::json;
+
+#[actix_web::test]
+async fn writes_past_the_limit_get_a_429_with_retry_after() {
+    let (app, _pool) = setup().await;
+    let app = app.app_data(web::Data::new(RateLimiter {
+        limit: 2,
+        windows: Mutex::new(HashMap::new()),
+    }));
+    let app = test::init_service(app).await;
+    let client: SocketAddr = "203.0.113.7:4000".parse().unwrap();
+
+    for (name, expected) in [
+        ("First", StatusCode::CREATED),
+        ("Second", StatusCode::CREATED),
+        ("Third", StatusCode::TOO_MANY_REQUESTS),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .peer_addr(client)
+            .set_json(json!({ "name": name, "price": 1 }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+        if expected == StatusCode::TOO_MANY_REQUESTS {
+            assert!(resp.headers().contains_key(header::RETRY_AFTER));
+        }
+    }
+
+    // Reads are never limited.
+    let req = test::TestRequest::get().uri("/products").peer_addr(client).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
@@ -0,0 +1,38 @@
+use super::*;
+use actix_web::test;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn incoming_request_id_is_echoed_in_header_and_error_body() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get()
+        .uri("/products/42")
+        .insert_header(("X-Request-Id", "client-abc-123"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers().get("x-request-id").unwrap(), "client-abc-123");
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["request_id"], "client-abc-123");
+}
+
+#[actix_web::test]
+async fn request_id_is_generated_when_missing_or_unusable() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let resp = test::call_service(&app, req).await;
+    let generated = resp.headers().get("x-request-id").unwrap().to_str().unwrap();
+    assert!(Uuid::parse_str(generated).is_ok());
+
+    let req = test::TestRequest::get()
+        .uri("/products")
+        .insert_header(("X-Request-Id", "has spaces"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let replaced = resp.headers().get("x-request-id").unwrap().to_str().unwrap();
+    assert!(Uuid::parse_str(replaced).is_ok());
+}
@@ -0,0 +1,10 @@
+use super::*;
+
+#[test]
+fn computed_amounts_serialize_with_two_decimals() {
+    #[derive(Serialize)]
+    struct Amount(#[serde(serialize_with = "rounded::serialize")] f64);
+
+    assert_eq!(serde_json::to_string(&Amount(19.9899999)).unwrap(), "19.99");
+    assert_eq!(serde_json::to_string(&Amount(0.1 + 0.2)).unwrap(), "0.3");
+}
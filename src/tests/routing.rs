@@ -0,0 +1,135 @@
+use super::*;
+use actix_web::test;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn unknown_routes_get_a_json_404() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/nonexistent").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "not found");
+    assert_eq!(body["code"], "not_found");
+    assert_eq!(body["path"], "/nonexistent");
+}
+
+#[actix_web::test]
+async fn json_endpoints_reject_other_content_types() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let body = r#"{"name": "Typed", "price": 1}"#;
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .insert_header((header::CONTENT_TYPE, "text/plain"))
+        .set_payload(body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let error: Value = test::read_body_json(resp).await;
+    assert_eq!(error["code"], "unsupported_media_type");
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .insert_header((header::CONTENT_TYPE, "application/json; charset=utf-8"))
+        .set_payload(body)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+}
+
+#[actix_web::test]
+async fn trailing_slashes_are_trimmed_before_routing() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product/")
+        .set_json(serde_json::json!({ "name": "Slashed", "price": 2.0 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let plain = test::call_and_read_body(&app, req).await;
+    let req = test::TestRequest::get().uri("/products/").to_request();
+    let slashed = test::call_and_read_body(&app, req).await;
+    assert_eq!(plain, slashed);
+
+    let req = test::TestRequest::delete().uri("/delete-product/1/").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get().uri("/products/1").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn cors_allowlist_lets_browsers_use_versions_and_idempotency_keys() {
+    let (app, _pool) = setup().await;
+    let origins = vec!["https://shop.example".to_string()];
+    let app = test::init_service(app.wrap(build_cors(Some(&origins)))).await;
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/products/1")
+        .insert_header((header::ORIGIN, "https://shop.example"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "PATCH"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "if-match, idempotency-key, x-request-id"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/products")
+        .insert_header((header::ORIGIN, "https://shop.example"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let exposed = resp
+        .headers()
+        .get(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+        .unwrap()
+        .to_str()
+        .unwrap();
+    for name in ["etag", "location", "retry-after", "x-request-id"] {
+        assert!(exposed.contains(name), "{} not exposed in {}", name, exposed);
+    }
+}
+
+#[actix_web::test]
+async fn malformed_json_gets_a_400_with_the_parse_error() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload("{bad json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "invalid JSON body");
+    assert_eq!(body["code"], "invalid_json");
+    assert!(!body["detail"].as_str().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn oversized_json_bodies_get_a_413() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let description = "x".repeat(DEFAULT_MAX_JSON_BODY);
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(serde_json::json!({ "name": "Huge", "price": 1, "description": description }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "payload_too_large");
+
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
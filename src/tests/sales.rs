@@ -0,0 +1,577 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+
+#[actix_web::test]
+async fn out_of_stock_sales_are_allowed_by_default() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Sold out", "price": 10, "quantity": 0 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": product.id,
+            "discount": 10,
+            "start_date": "2030-01-01",
+            "end_date": "2030-01-31"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+}
+
+#[actix_web::test]
+async fn out_of_stock_sales_are_rejected_when_stock_is_required() {
+    let (app, _pool) = setup().await;
+    let app = app.app_data(web::Data::new(SalePolicy {
+        require_stock: true,
+        max_discount: DEFAULT_MAX_DISCOUNT,
+    }));
+    let app = test::init_service(app).await;
+
+    for (name, quantity) in [("Sold out", 0), ("Stocked", 4)] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 10, "quantity": quantity }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let sale = |product_id: i32| {
+        test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": product_id,
+                "discount": 10,
+                "start_date": "2030-01-01",
+                "end_date": "2030-01-31"
+            }))
+            .to_request()
+    };
+    let resp = test::call_service(&app, sale(1)).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["error"].as_str().unwrap().contains("out of stock"));
+
+    assert_eq!(test::call_service(&app, sale(2)).await.status(), StatusCode::CREATED);
+}
+
+fn sale_request(product_id: i32, discount: i32, month: u32) -> test::TestRequest {
+    test::TestRequest::post().uri("/add-sale").set_json(json!({
+        "product_id": product_id,
+        "discount": discount,
+        "start_date": format!("2030-{:02}-01", month),
+        "end_date": format!("2030-{:02}-28", month)
+    }))
+}
+
+#[actix_web::test]
+async fn discounts_above_the_cap_are_unprocessable() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Capped", "price": 10, "quantity": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = sale_request(product.id, 90, 1).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = sale_request(product.id, 91, 2).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = test::TestRequest::put()
+        .uri("/sales/1")
+        .set_json(json!({
+            "product_id": product.id,
+            "discount": 95,
+            "start_date": "2030-01-01",
+            "end_date": "2030-01-28"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "discount may not exceed the 90% cap set by MAX_DISCOUNT");
+}
+
+#[actix_web::test]
+async fn max_discount_env_moves_the_cap() {
+    // No other test reads MAX_DISCOUNT, so setting it here can't race.
+    env::set_var("MAX_DISCOUNT", "50");
+    let policy = SalePolicy::from_env();
+    env::remove_var("MAX_DISCOUNT");
+    assert_eq!(policy.max_discount, 50);
+
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app.app_data(web::Data::new(policy))).await;
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Half off", "price": 10, "quantity": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    let req = sale_request(product.id, 50, 1).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    let req = sale_request(product.id, 51, 2).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[actix_web::test]
+async fn sales_can_be_filtered_to_a_date_window() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let mut ids = Vec::new();
+    for (name, start, end) in [
+        ("Inside", "2030-03-05", "2030-03-10"),
+        ("Overlapping", "2030-02-20", "2030-03-02"),
+        ("Outside", "2030-04-01", "2030-04-10"),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 10, "quantity": 1 }))
+            .to_request();
+        let product: Product = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": product.id,
+                "discount": 10,
+                "start_date": start,
+                "end_date": end
+            }))
+            .to_request();
+        let sale: Sale = test::call_and_read_body_json(&app, req).await;
+        ids.push(sale.id);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/sales?from=2030-03-01&to=2030-03-31")
+        .to_request();
+    let sales: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    let mut found: Vec<i32> = sales.iter().map(|sale| sale.id).collect();
+    found.sort();
+    assert_eq!(found, [ids[0], ids[1]]);
+
+    for uri in ["/sales?from=2030-03-31&to=2030-03-01", "/sales?from=March"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+async fn insert_sale(pool: &SqlitePool, product_id: i32, start: &str, end: &str) {
+    sqlx::query("INSERT INTO sales (product_id, discount, start_date, end_date) VALUES (?, 20, ?, ?)")
+        .bind(product_id)
+        .bind(start)
+        .bind(end)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn expired_sales_are_listed_and_archived_intact() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Seasonal", "price": 10, "quantity": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    insert_sale(&pool, product.id, "2020-01-01", "2020-01-31").await;
+    insert_sale(&pool, product.id, "2099-01-01", "2099-01-31").await;
+
+    let req = test::TestRequest::get().uri("/sales/expired").to_request();
+    let expired: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].end_date, "2020-01-31");
+
+    assert_eq!(archive_expired_sales(&pool, &today()).await.unwrap(), 1);
+    let archived: (i32, i32, i32, String, String) = sqlx::query_as(
+        "SELECT sale_id, product_id, discount, start_date, end_date FROM archived_sales",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        archived,
+        (
+            expired[0].id,
+            product.id,
+            20,
+            "2020-01-01".to_string(),
+            "2020-01-31".to_string()
+        )
+    );
+
+    let req = test::TestRequest::get().uri("/sales").to_request();
+    let remaining: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].start_date, "2099-01-01");
+}
+
+#[actix_web::test]
+async fn never_on_sale_skips_products_with_any_sale() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let mut ids = Vec::new();
+    for name in ["Promoted", "Neglected"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 5.0 }))
+            .to_request();
+        let created: Product = test::call_and_read_body_json(&app, req).await;
+        ids.push(created.id);
+    }
+    insert_sale(&pool, ids[0], "2030-01-01", "2030-01-31").await;
+
+    let req = test::TestRequest::get().uri("/products/never-on-sale").to_request();
+    let products: Vec<Product> = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<_> = products.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Neglected"]);
+}
+
+#[actix_web::test]
+async fn sales_summary_counts_sales_and_finds_the_latest_end() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["Promoted", "Plain"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 5.0 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    insert_sale(&pool, 1, "2030-03-01", "2030-03-31").await;
+    insert_sale(&pool, 1, "2030-01-01", "2030-01-31").await;
+
+    let req = test::TestRequest::get().uri("/products?include=sales_summary").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        page["items"][0]["sales_summary"],
+        json!({ "sale_count": 2, "last_sale_end": "2030-03-31" })
+    );
+    assert_eq!(
+        page["items"][1]["sales_summary"],
+        json!({ "sale_count": 0, "last_sale_end": null })
+    );
+
+    let req = test::TestRequest::get().uri("/products").to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(page["items"][0].get("sales_summary").is_none());
+}
+
+#[actix_web::test]
+async fn with_prices_adds_discounted_price_to_sales() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Headphones", "price": 19.99, "quantity": 2 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    insert_sale(&pool, product.id, "2030-01-01", "2030-01-31").await;
+
+    let req = test::TestRequest::get().uri("/sales").to_request();
+    let lean: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(lean[0].get("final_price").is_none());
+
+    let req = test::TestRequest::get().uri("/sales?with_prices=true").to_request();
+    let priced: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(priced.len(), 1);
+    assert_eq!(priced[0]["product_id"], product.id);
+    assert_eq!(priced[0]["discount"], 20);
+    assert_eq!(priced[0]["product_name"], "Headphones");
+    assert_eq!(priced[0]["base_price"], 19.99);
+    assert_eq!(priced[0]["final_price"], 15.99);
+}
+
+#[actix_web::test]
+async fn sale_writes_refresh_cached_sales_summaries() {
+    let (app, _pool) = setup().await;
+    let app = app.app_data(web::Data::new(ProductsCache {
+        ttl: Duration::from_secs(60),
+        state: Mutex::new(ProductsCacheState::default()),
+    }));
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Cached", "price": 5.0 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let sale_count = |page: Value| page["items"][0]["sales_summary"]["sale_count"].clone();
+
+    let req = test::TestRequest::get().uri("/products?include=sales_summary").to_request();
+    assert_eq!(sale_count(test::call_and_read_body_json(&app, req).await), 0);
+
+    let req = test::TestRequest::post()
+        .uri("/add-sale")
+        .set_json(json!({
+            "product_id": product.id,
+            "discount": 10,
+            "start_date": "2030-01-01",
+            "end_date": "2030-01-31"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let req = test::TestRequest::get().uri("/products?include=sales_summary").to_request();
+    assert_eq!(sale_count(test::call_and_read_body_json(&app, req).await), 1);
+}
+
+#[actix_web::test]
+async fn discounts_must_be_percentages() {
+    let (app, _pool) = setup().await;
+    // Lift the finance cap so only the 0–100 bounds check applies.
+    let app = app.app_data(web::Data::new(SalePolicy {
+        require_stock: false,
+        max_discount: 100,
+    }));
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Bounded", "price": 10, "quantity": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+
+    for (month, discount, expected) in [
+        (1, 0, StatusCode::CREATED),
+        (2, 100, StatusCode::CREATED),
+        (3, 101, StatusCode::BAD_REQUEST),
+        (4, -1, StatusCode::BAD_REQUEST),
+    ] {
+        let req = sale_request(product.id, discount, month).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), expected, "discount {}", discount);
+    }
+}
+
+#[actix_web::test]
+async fn sale_dates_are_validated_and_normalized() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Dated", "price": 10, "quantity": 1 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let sale = |start: &str, end: &str| {
+        test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": product.id,
+                "discount": 10,
+                "start_date": start,
+                "end_date": end
+            }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, sale("not-a-date", "2030-01-31")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "start_date must be a date in YYYY-MM-DD format");
+
+    let resp = test::call_service(&app, sale("2030-01-31", "2030-01-01")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let created: Sale = test::call_and_read_body_json(&app, sale("2030-1-5", "2030-1-20")).await;
+    assert_eq!(created.start_date, "2030-01-05");
+    assert_eq!(created.end_date, "2030-01-20");
+}
+
+#[actix_web::test]
+async fn active_sales_are_the_ones_running_today() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Timed", "price": 10 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let today = Utc::now().date_naive();
+    let yesterday = today.pred_opt().unwrap().to_string();
+    let tomorrow = today.succ_opt().unwrap().to_string();
+    insert_sale(&pool, product.id, "2020-01-01", &yesterday).await;
+    insert_sale(&pool, product.id, &tomorrow, "2099-12-31").await;
+    insert_sale(&pool, product.id, &today.to_string(), &today.to_string()).await;
+
+    let req = test::TestRequest::get().uri("/sales/active").to_request();
+    let active: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].id, 3);
+}
+
+#[actix_web::test]
+async fn overlapping_sales_for_a_product_are_conflicts() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Contested", "price": 10 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let sale = |start: &str, end: &str| {
+        test::TestRequest::post()
+            .uri("/add-sale")
+            .set_json(json!({
+                "product_id": product.id,
+                "discount": 10,
+                "start_date": start,
+                "end_date": end
+            }))
+            .to_request()
+    };
+    assert_eq!(
+        test::call_service(&app, sale("2030-01-10", "2030-01-20")).await.status(),
+        StatusCode::CREATED
+    );
+
+    for (start, end, expected) in [
+        ("2030-01-01", "2030-01-31", StatusCode::CONFLICT),
+        ("2030-01-15", "2030-01-25", StatusCode::CONFLICT),
+        // Both ends are inclusive, so sharing a single day still overlaps.
+        ("2030-01-05", "2030-01-10", StatusCode::CONFLICT),
+        ("2030-01-21", "2030-01-31", StatusCode::CREATED),
+    ] {
+        let resp = test::call_service(&app, sale(start, end)).await;
+        assert_eq!(resp.status(), expected, "{} to {}", start, end);
+    }
+}
+
+#[actix_web::test]
+async fn deleting_a_product_takes_its_sales_out_of_the_listings() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Retiring", "price": 10 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    insert_sale(&pool, product.id, "2099-01-01", "2099-01-31").await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/delete-product/{}", product.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    for uri in ["/sales/upcoming", "/sales", "/sales?with_prices=true"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let listed: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(listed, json!([]), "{}", uri);
+    }
+    let req = test::TestRequest::get().uri("/sales/1").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    // Deletes are soft, so the sale is kept for history and comes back on restore.
+    let (kept,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sales WHERE product_id = ?")
+        .bind(product.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(kept, 1);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/products/{}/restore", product.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::get().uri("/sales/upcoming").to_request();
+    let upcoming: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(upcoming.len(), 1);
+    let req = test::TestRequest::get().uri("/sales").to_request();
+    let listed: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.len(), 1);
+    let req = test::TestRequest::get().uri("/sales/1").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn added_sales_are_committed_and_missing_products_leave_no_row() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Committed", "price": 10 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let created: Sale =
+        test::call_and_read_body_json(&app, sale_request(product.id, 10, 1).to_request()).await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/sales/{}", created.id))
+        .to_request();
+    let fetched: Sale = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fetched.product_id, product.id);
+
+    let req = sale_request(999, 10, 2).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sales")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[actix_web::test]
+async fn sales_can_be_filtered_to_one_product() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    for name in ["Promoted", "Other"] {
+        let req = test::TestRequest::post()
+            .uri("/add-product")
+            .set_json(json!({ "name": name, "price": 10 }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+    insert_sale(&pool, 1, "2030-01-01", "2030-01-31").await;
+    insert_sale(&pool, 2, "2030-01-01", "2030-01-31").await;
+    insert_sale(&pool, 1, "2030-03-01", "2030-03-31").await;
+
+    let req = test::TestRequest::get().uri("/sales?product_id=1").to_request();
+    let sales: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    let mut ids: Vec<i32> = sales.iter().map(|sale| sale.id).collect();
+    ids.sort();
+    assert_eq!(ids, [1, 3]);
+
+    let req = test::TestRequest::get().uri("/sales").to_request();
+    let all: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(all.len(), 3);
+}
+
+#[actix_web::test]
+async fn upcoming_sales_start_after_today() {
+    let (app, pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Previewed", "price": 10 }))
+        .to_request();
+    let product: Product = test::call_and_read_body_json(&app, req).await;
+    let today = Utc::now().date_naive();
+    let yesterday = today.pred_opt().unwrap().to_string();
+    let tomorrow = today.succ_opt().unwrap().to_string();
+    insert_sale(&pool, product.id, &tomorrow, "2099-12-31").await;
+    insert_sale(&pool, product.id, &yesterday, &tomorrow).await;
+
+    let req = test::TestRequest::get().uri("/sales/upcoming").to_request();
+    let upcoming: Vec<Sale> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0].start_date, tomorrow);
+}
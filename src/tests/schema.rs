@@ -0,0 +1,107 @@
+use super::*;
+use actix_web::test;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn body_schemas_carry_the_validation_bounds() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/schema/product").to_request();
+    let product: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(product["properties"]["price"]["minimum"], 0.0);
+    assert_eq!(
+        product["properties"]["status"]["allOf"][0]["enum"],
+        serde_json::json!(["active", "discontinued", "out_of_stock"])
+    );
+
+    let req = test::TestRequest::get().uri("/schema/sale").to_request();
+    let sale: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(sale["properties"]["discount"]["minimum"], 0.0);
+    assert_eq!(sale["properties"]["discount"]["maximum"], 100.0);
+    assert_eq!(sale["properties"]["start_date"]["format"], "date");
+}
+
+#[actix_web::test]
+async fn openapi_lists_every_route() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/api-docs/openapi.json").to_request();
+    let spec: Value = test::call_and_read_body_json(&app, req).await;
+    let documented = |method: &str, path: &str| spec["paths"][path][method].is_object();
+    for (method, path) in [
+        ("get", "/products"),
+        ("get", "/products.csv"),
+        ("get", "/products/count"),
+        ("get", "/products/batch"),
+        ("get", "/products/low-stock"),
+        ("get", "/products/never-on-sale"),
+        ("get", "/products/by-slug/{slug}"),
+        ("post", "/products/bulk"),
+        ("post", "/products/bulk-delete"),
+        ("get", "/products/{id}"),
+        ("patch", "/products/{id}"),
+        ("get", "/products/{id}/price"),
+        ("get", "/products/{id}/price-history"),
+        ("patch", "/products/{id}/stock"),
+        ("post", "/products/{id}/adjust-stock"),
+        ("post", "/products/{id}/reserve"),
+        ("post", "/products/{id}/restore"),
+        ("post", "/products/{id}/clone"),
+        ("post", "/products/{id}/tags"),
+        ("delete", "/products/{id}/tags/{tag}"),
+        ("post", "/add-product"),
+        ("put", "/update-product/{id}"),
+        ("delete", "/delete-product/{id}"),
+        ("post", "/add-sale"),
+        ("delete", "/delete-sale/{id}"),
+        ("get", "/sales"),
+        ("get", "/sales/active"),
+        ("get", "/sales/upcoming"),
+        ("get", "/sales/expired"),
+        ("get", "/sales/{id}"),
+        ("put", "/sales/{id}"),
+        ("patch", "/sales/{id}/active"),
+        ("get", "/categories"),
+        ("post", "/categories"),
+        ("delete", "/categories/{id}"),
+        ("get", "/catalog"),
+        ("get", "/deals"),
+        ("get", "/stats"),
+        ("get", "/stats/inventory-value"),
+        ("get", "/health"),
+        ("get", "/livez"),
+        ("get", "/version"),
+        ("get", "/readyz"),
+        ("get", "/metrics"),
+        ("get", "/export"),
+        ("post", "/import"),
+        ("get", "/admin/db-info"),
+        ("post", "/admin/vacuum"),
+        ("get", READ_ONLY_TOGGLE_PATH),
+        ("put", READ_ONLY_TOGGLE_PATH),
+        ("get", "/schema/product"),
+        ("get", "/schema/sale"),
+    ] {
+        assert!(documented(method, path), "{} {} is missing from the spec", method, path);
+    }
+}
+
+#[actix_web::test]
+async fn sales_list_documents_both_shapes() {
+    let (app, _pool) = setup().await;
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::get().uri("/api-docs/openapi.json").to_request();
+    let spec: Value = test::call_and_read_body_json(&app, req).await;
+    let mut shapes: Vec<&str> = spec["components"]["schemas"]["SaleList"]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|shape| shape["items"]["$ref"].as_str().unwrap())
+        .collect();
+    shapes.sort();
+    assert_eq!(shapes, ["#/components/schemas/PricedSale", "#/components/schemas/Sale"]);
+    assert!(spec["components"]["schemas"]["PricedSale"]["allOf"].is_array());
+}
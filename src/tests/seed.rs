@@ -0,0 +1,19 @@
+use super::*;
+
+#[actix_web::test]
+async fn seeding_twice_adds_nothing_the_second_time() {
+    let pool = test_pool().await;
+
+    let first = seed_demo_data(&pool).await.unwrap();
+    assert_eq!(first.products, DEMO_PRODUCTS.len());
+    assert_eq!(first.sales, DEMO_SALES.len());
+
+    let second = seed_demo_data(&pool).await.unwrap();
+    assert_eq!(second, SeedSummary::default());
+
+    let (products,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM products")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(products as usize, DEMO_PRODUCTS.len());
+}
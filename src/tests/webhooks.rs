@@ -0,0 +1,85 @@
+use super::*;
+use actix_web::test;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Accepts one HTTP request, answers 200 and returns its JSON body.
+async fn receive_one(listener: TcpListener) -> Value {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&request);
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if body.len() >= length {
+                socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await
+                    .unwrap();
+                return serde_json::from_str(body).unwrap();
+            }
+        }
+    }
+}
+
+#[actix_web::test]
+async fn created_products_are_posted_to_the_webhook() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = rt::spawn(receive_one(listener));
+
+    let (app, _pool) = setup().await;
+    let app = app.app_data(web::Data::new(Webhooks::new(Some(url))));
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/add-product")
+        .set_json(json!({ "name": "Kettle", "price": 25.0 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+
+    let event = tokio::time::timeout(Duration::from_secs(5), received)
+        .await
+        .expect("the webhook was delivered")
+        .unwrap();
+    assert_eq!(event["type"], "product.created");
+    assert_eq!(event["data"]["name"], "Kettle");
+}
+
+#[actix_web::test]
+async fn stock_adjustments_are_posted_as_product_updates() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = rt::spawn(receive_one(listener));
+
+    let (app, pool) = setup().await;
+    // Inserted directly so the only event is the one under test.
+    sqlx::query("INSERT INTO products (name, price_cents, in_stock, quantity) VALUES ('Kettle', 2500, 1, 3)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = app.app_data(web::Data::new(Webhooks::new(Some(url))));
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/products/1/adjust-stock")
+        .set_json(json!({ "delta": -2 }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let event = tokio::time::timeout(Duration::from_secs(5), received)
+        .await
+        .expect("the webhook was delivered")
+        .unwrap();
+    assert_eq!(event["type"], "product.updated");
+    assert_eq!(event["data"]["quantity"], 1);
+}